@@ -0,0 +1,406 @@
+//! CLI argument parsing and on-disk persistence of user-facing settings:
+//! the stream URL, window identity, initial filter pipeline state, source
+//! type, and reconnect backoff parameters. Settings are handed to
+//! `XCoffee::new` as `iced::Application::Flags` and written back out
+//! whenever they change, so the app reopens with the same source and
+//! filter choices.
+
+use crate::pipeline::FilterKind;
+use crate::source::RtpVideoCodec;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+/// Path the config file is read from and written to. Kept next to the
+/// working directory, matching `timelapse_output_path`'s convention of
+/// writing plain files alongside wherever the app is run from.
+const CONFIG_PATH: &str = "xcoffee.conf";
+
+#[derive(Debug, Clone, Copy)]
+pub struct BackoffConfig {
+    pub base: Duration,
+    pub max: Duration,
+}
+
+impl Default for BackoffConfig {
+    fn default() -> Self {
+        Self {
+            base: Duration::from_secs(1),
+            max: Duration::from_secs(30),
+        }
+    }
+}
+
+/// The filter pipeline's adjustable parameters, mirroring the fields on
+/// `XCoffee` so a loaded config can be applied directly in `new`.
+#[derive(Debug, Clone)]
+pub struct FilterSettings {
+    pub grayscale: bool,
+    pub target_size: u32,
+    pub filter_kind: FilterKind,
+    pub jpeg_quality: u8,
+    pub posterize_enabled: bool,
+    pub posterize_levels: u8,
+    pub timestamp_overlay: bool,
+}
+
+impl Default for FilterSettings {
+    fn default() -> Self {
+        Self {
+            grayscale: false,
+            target_size: 128,
+            filter_kind: FilterKind::Nearest,
+            jpeg_quality: 50,
+            posterize_enabled: false,
+            posterize_levels: 4,
+            timestamp_overlay: false,
+        }
+    }
+}
+
+/// The timelapse recorder's adjustable parameters: output frame rate and
+/// how sparsely frames are sampled from the live stream. A
+/// `capture_interval_secs` of `0` means "every tick", matching
+/// `RecorderState::new`'s `None`.
+#[derive(Debug, Clone, Copy)]
+pub struct RecorderSettings {
+    pub output_fps: u32,
+    pub capture_interval_secs: u32,
+}
+
+impl RecorderSettings {
+    pub fn capture_interval(&self) -> Option<Duration> {
+        if self.capture_interval_secs == 0 {
+            None
+        } else {
+            Some(Duration::from_secs(self.capture_interval_secs as u64))
+        }
+    }
+}
+
+impl Default for RecorderSettings {
+    fn default() -> Self {
+        Self {
+            output_fps: crate::recorder::DEFAULT_OUTPUT_FPS,
+            capture_interval_secs: crate::recorder::DEFAULT_CAPTURE_INTERVAL
+                .map(|d| d.as_secs() as u32)
+                .unwrap_or(0),
+        }
+    }
+}
+
+/// Which `FrameSource` implementation to connect with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourceKind {
+    HttpMjpeg,
+    Rtp,
+}
+
+/// Everything needed to build a `SourceConfig`, regardless of which
+/// `kind` is actually selected; the unused variant's fields are simply
+/// ignored.
+#[derive(Debug, Clone, Copy)]
+pub struct SourceSettings {
+    pub kind: SourceKind,
+    pub rtp_bind_addr: SocketAddr,
+    pub rtp_codec: RtpVideoCodec,
+}
+
+impl Default for SourceSettings {
+    fn default() -> Self {
+        Self {
+            kind: SourceKind::HttpMjpeg,
+            rtp_bind_addr: ([0, 0, 0, 0], 5004).into(),
+            rtp_codec: RtpVideoCodec::Vp8,
+        }
+    }
+}
+
+/// Settings passed into `XCoffee::new` as `Application::Flags`.
+#[derive(Debug, Clone)]
+pub struct AppConfig {
+    pub url: String,
+    pub window_title: String,
+    pub window_id: String,
+    pub initial_filter: FilterSettings,
+    pub backoff: BackoffConfig,
+    pub source: SourceSettings,
+    pub recorder: RecorderSettings,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            url: "https://kaffee.hnf.de".to_string(),
+            window_title: "xcoffee".to_string(),
+            window_id: "xcoffee".to_string(),
+            initial_filter: FilterSettings::default(),
+            backoff: BackoffConfig::default(),
+            source: SourceSettings::default(),
+            recorder: RecorderSettings::default(),
+        }
+    }
+}
+
+/// Builds the effective config: start from the persisted file (if any),
+/// then apply any CLI overrides on top.
+pub fn resolve() -> AppConfig {
+    let mut config = load().unwrap_or_default();
+    apply_args(&mut config, std::env::args().skip(1));
+    config
+}
+
+/// Parses `--url`, `--title`, `--window-id`, `--backoff-base-ms`,
+/// `--backoff-max-ms`, `--source`, `--rtp-bind`, `--rtp-codec`,
+/// `--record-fps`, `--record-interval-secs`, and the initial-filter flags
+/// (`--grayscale`, `--target-size`, `--filter-kind`, `--jpeg-quality`,
+/// `--posterize`, `--posterize-levels`, `--timestamp-overlay`) out of an
+/// argument iterator, overwriting only the fields that were actually
+/// passed.
+fn apply_args(config: &mut AppConfig, args: impl Iterator<Item = String>) {
+    let args: Vec<String> = args.collect();
+    let value_after = |flag: &str| -> Option<&str> {
+        args.iter()
+            .position(|a| a == flag)
+            .and_then(|idx| args.get(idx + 1))
+            .map(String::as_str)
+    };
+    let has_flag = |flag: &str| -> bool { args.iter().any(|a| a == flag) };
+
+    if let Some(url) = value_after("--url") {
+        config.url = url.to_string();
+    }
+    if let Some(title) = value_after("--title") {
+        config.window_title = title.to_string();
+    }
+    if let Some(id) = value_after("--window-id") {
+        config.window_id = id.to_string();
+    }
+    if let Some(ms) = value_after("--backoff-base-ms").and_then(|s| s.parse().ok()) {
+        config.backoff.base = Duration::from_millis(ms);
+    }
+    if let Some(ms) = value_after("--backoff-max-ms").and_then(|s| s.parse().ok()) {
+        config.backoff.max = Duration::from_millis(ms);
+    }
+    if let Some(kind) = value_after("--source").and_then(parse_source_kind) {
+        config.source.kind = kind;
+    }
+    if let Some(addr) = value_after("--rtp-bind").and_then(|s| s.parse().ok()) {
+        config.source.rtp_bind_addr = addr;
+    }
+    if let Some(codec) = value_after("--rtp-codec").and_then(parse_rtp_codec) {
+        config.source.rtp_codec = codec;
+    }
+    if let Some(fps) = value_after("--record-fps").and_then(|s| s.parse().ok()) {
+        config.recorder.output_fps = clamp_output_fps(fps);
+    }
+    if let Some(secs) = value_after("--record-interval-secs").and_then(|s| s.parse().ok()) {
+        config.recorder.capture_interval_secs = secs;
+    }
+
+    if has_flag("--grayscale") {
+        config.initial_filter.grayscale = true;
+    }
+    if let Some(v) = value_after("--target-size").and_then(|s| s.parse().ok()) {
+        config.initial_filter.target_size = v;
+    }
+    if let Some(kind) = value_after("--filter-kind").and_then(parse_filter_kind) {
+        config.initial_filter.filter_kind = kind;
+    }
+    if let Some(v) = value_after("--jpeg-quality").and_then(|s| s.parse().ok()) {
+        config.initial_filter.jpeg_quality = clamp_jpeg_quality(v);
+    }
+    if has_flag("--posterize") {
+        config.initial_filter.posterize_enabled = true;
+    }
+    if let Some(v) = value_after("--posterize-levels").and_then(|s| s.parse().ok()) {
+        config.initial_filter.posterize_levels = v;
+    }
+    if has_flag("--timestamp-overlay") {
+        config.initial_filter.timestamp_overlay = true;
+    }
+}
+
+/// Reads `xcoffee.conf`'s `key=value` lines, falling back to `None` (and
+/// thus `AppConfig::default()`) if the file is missing or unreadable.
+fn load() -> Option<AppConfig> {
+    let contents = std::fs::read_to_string(CONFIG_PATH).ok()?;
+    let mut config = AppConfig::default();
+
+    for line in contents.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let (key, value) = (key.trim(), value.trim());
+        match key {
+            "url" => config.url = value.to_string(),
+            "window_title" => config.window_title = value.to_string(),
+            "window_id" => config.window_id = value.to_string(),
+            "grayscale" => config.initial_filter.grayscale = value == "true",
+            "target_size" => {
+                if let Ok(v) = value.parse() {
+                    config.initial_filter.target_size = v;
+                }
+            }
+            "filter_kind" => {
+                if let Some(kind) = parse_filter_kind(value) {
+                    config.initial_filter.filter_kind = kind;
+                }
+            }
+            "jpeg_quality" => {
+                if let Ok(v) = value.parse() {
+                    config.initial_filter.jpeg_quality = clamp_jpeg_quality(v);
+                }
+            }
+            "posterize_enabled" => config.initial_filter.posterize_enabled = value == "true",
+            "posterize_levels" => {
+                if let Ok(v) = value.parse() {
+                    config.initial_filter.posterize_levels = v;
+                }
+            }
+            "timestamp_overlay" => config.initial_filter.timestamp_overlay = value == "true",
+            "backoff_base_ms" => {
+                if let Ok(v) = value.parse() {
+                    config.backoff.base = Duration::from_millis(v);
+                }
+            }
+            "backoff_max_ms" => {
+                if let Ok(v) = value.parse() {
+                    config.backoff.max = Duration::from_millis(v);
+                }
+            }
+            "source_kind" => {
+                if let Some(kind) = parse_source_kind(value) {
+                    config.source.kind = kind;
+                }
+            }
+            "rtp_bind_addr" => {
+                if let Ok(addr) = value.parse() {
+                    config.source.rtp_bind_addr = addr;
+                }
+            }
+            "rtp_codec" => {
+                if let Some(codec) = parse_rtp_codec(value) {
+                    config.source.rtp_codec = codec;
+                }
+            }
+            "record_fps" => {
+                if let Ok(v) = value.parse() {
+                    config.recorder.output_fps = clamp_output_fps(v);
+                }
+            }
+            "record_interval_secs" => {
+                if let Ok(v) = value.parse() {
+                    config.recorder.capture_interval_secs = v;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Some(config)
+}
+
+/// Writes the current settings back to `xcoffee.conf`. Errors are not
+/// fatal to the caller; a failed save just means the next launch falls
+/// back to defaults or CLI args.
+pub fn save(config: &AppConfig) -> std::io::Result<()> {
+    let contents = format!(
+        "url={}\n\
+         window_title={}\n\
+         window_id={}\n\
+         grayscale={}\n\
+         target_size={}\n\
+         filter_kind={}\n\
+         jpeg_quality={}\n\
+         posterize_enabled={}\n\
+         posterize_levels={}\n\
+         timestamp_overlay={}\n\
+         backoff_base_ms={}\n\
+         backoff_max_ms={}\n\
+         source_kind={}\n\
+         rtp_bind_addr={}\n\
+         rtp_codec={}\n\
+         record_fps={}\n\
+         record_interval_secs={}\n",
+        config.url,
+        config.window_title,
+        config.window_id,
+        config.initial_filter.grayscale,
+        config.initial_filter.target_size,
+        filter_kind_name(config.initial_filter.filter_kind),
+        config.initial_filter.jpeg_quality,
+        config.initial_filter.posterize_enabled,
+        config.initial_filter.posterize_levels,
+        config.initial_filter.timestamp_overlay,
+        config.backoff.base.as_millis(),
+        config.backoff.max.as_millis(),
+        source_kind_name(config.source.kind),
+        config.source.rtp_bind_addr,
+        rtp_codec_name(config.source.rtp_codec),
+        config.recorder.output_fps,
+        config.recorder.capture_interval_secs,
+    );
+    std::fs::write(CONFIG_PATH, contents)
+}
+
+fn filter_kind_name(kind: FilterKind) -> &'static str {
+    match kind {
+        FilterKind::Nearest => "nearest",
+        FilterKind::Triangle => "triangle",
+        FilterKind::Lanczos3 => "lanczos3",
+    }
+}
+
+fn parse_filter_kind(value: &str) -> Option<FilterKind> {
+    match value {
+        "nearest" => Some(FilterKind::Nearest),
+        "triangle" => Some(FilterKind::Triangle),
+        "lanczos3" => Some(FilterKind::Lanczos3),
+        _ => None,
+    }
+}
+
+fn source_kind_name(kind: SourceKind) -> &'static str {
+    match kind {
+        SourceKind::HttpMjpeg => "mjpeg",
+        SourceKind::Rtp => "rtp",
+    }
+}
+
+fn parse_source_kind(value: &str) -> Option<SourceKind> {
+    match value {
+        "mjpeg" => Some(SourceKind::HttpMjpeg),
+        "rtp" => Some(SourceKind::Rtp),
+        _ => None,
+    }
+}
+
+fn rtp_codec_name(codec: RtpVideoCodec) -> &'static str {
+    match codec {
+        RtpVideoCodec::Vp8 => "vp8",
+        RtpVideoCodec::Vp9 => "vp9",
+    }
+}
+
+fn parse_rtp_codec(value: &str) -> Option<RtpVideoCodec> {
+    match value {
+        "vp8" => Some(RtpVideoCodec::Vp8),
+        "vp9" => Some(RtpVideoCodec::Vp9),
+        _ => None,
+    }
+}
+
+/// Clamps to the UI slider's `1..=60` range. `output_fps` ends up as the
+/// divisor of `Duration::from_secs_f64` in the capture-tick subscription,
+/// so `0` (reachable via CLI/config, unlike the slider) would produce an
+/// infinite duration and panic.
+fn clamp_output_fps(fps: u32) -> u32 {
+    fps.clamp(1, 60)
+}
+
+/// Clamps to the UI slider's `1..=100` range. `0` would ask the JPEG
+/// encoder for zero quality, which is meaningless even though it wouldn't
+/// panic outright.
+fn clamp_jpeg_quality(quality: u8) -> u8 {
+    quality.clamp(1, 100)
+}
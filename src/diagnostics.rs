@@ -0,0 +1,118 @@
+//! Stream diagnostics: rolling FPS/size stats derived from frame arrival
+//! timestamps, plus the parser health counters surfaced by `MjpegDecoder`.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// How many recent frames to keep timestamps/sizes for when computing
+/// rolling FPS and size averages.
+const HISTORY_LEN: usize = 60;
+
+/// High-level phase of the upstream connection, mirrored from the
+/// subscription's internal state machine for display in the UI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionPhase {
+    Connecting,
+    Streaming,
+    Sleeping,
+}
+
+impl std::fmt::Display for ConnectionPhase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let label = match self {
+            ConnectionPhase::Connecting => "Connecting",
+            ConnectionPhase::Streaming => "Streaming",
+            ConnectionPhase::Sleeping => "Sleeping",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+struct FrameSample {
+    at: Instant,
+    size: usize,
+}
+
+/// Ring buffer of recent frame arrivals plus the decoder's cumulative
+/// health counters, used to compute the numbers shown in the diagnostics
+/// panel.
+pub struct StreamDiagnostics {
+    history: VecDeque<FrameSample>,
+    cumulative_bytes: u64,
+    malformed_parts: u64,
+    connection_phase: ConnectionPhase,
+}
+
+impl Default for StreamDiagnostics {
+    fn default() -> Self {
+        Self {
+            history: VecDeque::with_capacity(HISTORY_LEN),
+            cumulative_bytes: 0,
+            malformed_parts: 0,
+            connection_phase: ConnectionPhase::Connecting,
+        }
+    }
+}
+
+impl StreamDiagnostics {
+    pub fn set_connection_phase(&mut self, phase: ConnectionPhase) {
+        self.connection_phase = phase;
+    }
+
+    pub fn connection_phase(&self) -> ConnectionPhase {
+        self.connection_phase
+    }
+
+    /// Records a newly decoded frame, along with the decoder's cumulative
+    /// byte/malformed-part counters at the time it was extracted.
+    pub fn record_frame(&mut self, size: usize, cumulative_bytes: u64, malformed_parts: u64) {
+        if self.history.len() == HISTORY_LEN {
+            self.history.pop_front();
+        }
+        self.history.push_back(FrameSample {
+            at: Instant::now(),
+            size,
+        });
+        self.cumulative_bytes = cumulative_bytes;
+        self.malformed_parts = malformed_parts;
+    }
+
+    pub fn cumulative_bytes(&self) -> u64 {
+        self.cumulative_bytes
+    }
+
+    pub fn malformed_parts(&self) -> u64 {
+        self.malformed_parts
+    }
+
+    pub fn last_frame_size(&self) -> Option<usize> {
+        self.history.back().map(|s| s.size)
+    }
+
+    pub fn average_frame_size(&self) -> Option<usize> {
+        if self.history.is_empty() {
+            return None;
+        }
+        let total: usize = self.history.iter().map(|s| s.size).sum();
+        Some(total / self.history.len())
+    }
+
+    pub fn peak_frame_size(&self) -> Option<usize> {
+        self.history.iter().map(|s| s.size).max()
+    }
+
+    /// Frames per second measured from the span between the oldest and
+    /// newest timestamps currently in the history.
+    pub fn fps(&self) -> Option<f64> {
+        if self.history.len() < 2 {
+            return None;
+        }
+        let oldest = self.history.front()?.at;
+        let newest = self.history.back()?.at;
+        let span = newest.duration_since(oldest);
+        if span == Duration::ZERO {
+            return None;
+        }
+        Some((self.history.len() - 1) as f64 / span.as_secs_f64())
+    }
+}
@@ -1,62 +1,172 @@
-use bytes::Bytes;
-use futures_util::{Stream, StreamExt};
 use iced::{
-    widget::{checkbox, column, container, text, Image},
+    widget::{checkbox, column, container, pick_list, row, slider, text, Image},
     Application, Command, Element, Length, Settings, Subscription, Theme,
 };
-use image::{imageops, DynamicImage, ImageFormat};
-use reqwest;
-use std::io::Cursor;
+use std::net::SocketAddr;
 use std::time::Duration;
 
+mod config;
+mod diagnostics;
+mod mjpeg;
+mod pipeline;
+mod recorder;
+mod relay;
+mod rtp;
+mod source;
+
+use config::AppConfig;
+use diagnostics::{ConnectionPhase, StreamDiagnostics};
+use pipeline::{FilterKind, FilterStage};
+use recorder::RecorderState;
+use source::{FrameSource, SourceConfig};
+
 fn main() -> iced::Result {
+    let app_config = config::resolve();
+
+    if let Some(bind_addr) = serve_bind_addr_from_args() {
+        return run_relay(bind_addr, app_config.url);
+    }
+
     let settings = Settings {
-        id: Some("xcoffee".to_string()),
+        id: Some(app_config.window_id.clone()),
+        flags: app_config,
         ..Settings::default()
     };
     XCoffee::run(settings)
 }
 
+/// Looks for `--serve [addr]` in the process args, e.g. `--serve
+/// 0.0.0.0:8080`. Defaults to `127.0.0.1:8080` when no address is given.
+fn serve_bind_addr_from_args() -> Option<SocketAddr> {
+    let args: Vec<String> = std::env::args().collect();
+    let idx = args.iter().position(|a| a == "--serve")?;
+    Some(
+        args.get(idx + 1)
+            .and_then(|s| s.parse().ok())
+            .unwrap_or_else(|| ([127, 0, 0, 1], 8080).into()),
+    )
+}
+
+/// Runs the headless re-broadcast proxy instead of the iced GUI.
+fn run_relay(bind_addr: SocketAddr, upstream_url: String) -> iced::Result {
+    let rt = tokio::runtime::Runtime::new().expect("failed to start tokio runtime");
+    rt.block_on(async {
+        if let Err(e) = relay::serve(upstream_url, bind_addr).await {
+            eprintln!("relay error: {}", e);
+        }
+    });
+    Ok(())
+}
+
+/// A decoded JPEG part plus the decoder's cumulative health counters at the
+/// moment it was extracted, so the diagnostics panel can track frame size
+/// and malformed-part history without re-parsing anything itself.
+#[derive(Debug, Clone)]
+struct FrameEvent {
+    data: Vec<u8>,
+    cumulative_bytes: u64,
+    malformed_parts: u64,
+}
+
 #[derive(Debug, Clone)]
 enum Message {
-    ImageLoaded(Result<Vec<u8>, String>),
+    ImageLoaded(Result<FrameEvent, String>),
     Error(String),
-    ToggleTrojanView(bool),
+    ToggleRecording(bool),
+    RecordTick,
+    RecorderFpsChanged(u32),
+    RecorderIntervalChanged(u32),
+    PersistConfigTick,
+    ToggleDiagnostics(bool),
+    ConnectionPhaseChanged(ConnectionPhase),
+    ToggleGrayscale(bool),
+    ResolutionChanged(u32),
+    FilterKindChanged(FilterKind),
+    QualityChanged(u8),
+    TogglePosterize(bool),
+    PosterizeLevelsChanged(u8),
+    ToggleTimestampOverlay(bool),
 }
 
 struct XCoffee {
+    url: String,
+    window_title: String,
+    window_id: String,
+    backoff: config::BackoffConfig,
+    source: config::SourceSettings,
     image_data: Option<Vec<u8>>,
     status: String,
     loading: bool,
-    trojan_view: bool,
+    recording_enabled: bool,
+    recorder: Option<RecorderState>,
+    recorder_settings: config::RecorderSettings,
+    /// Set whenever a setting changes; cleared by `PersistConfigTick`,
+    /// which is what actually writes `xcoffee.conf`. Keeps a dragged
+    /// slider from triggering a blocking disk write on every tick.
+    config_dirty: bool,
+    diagnostics_open: bool,
+    diagnostics: StreamDiagnostics,
+    grayscale: bool,
+    target_size: u32,
+    filter_kind: FilterKind,
+    jpeg_quality: u8,
+    posterize_enabled: bool,
+    posterize_levels: u8,
+    timestamp_overlay: bool,
 }
 
 impl Application for XCoffee {
     type Executor = iced::executor::Default;
     type Message = Message;
     type Theme = Theme;
-    type Flags = ();
+    type Flags = AppConfig;
 
-    fn new(_flags: ()) -> (Self, Command<Message>) {
+    fn new(flags: AppConfig) -> (Self, Command<Message>) {
+        let filter = flags.initial_filter;
         (
             Self {
+                url: flags.url,
+                window_title: flags.window_title,
+                window_id: flags.window_id,
+                backoff: flags.backoff,
+                source: flags.source,
                 image_data: None,
                 status: "Connecting to stream...".to_string(),
                 loading: true,
-                trojan_view: false,
+                recording_enabled: false,
+                recorder: None,
+                recorder_settings: flags.recorder,
+                config_dirty: false,
+                diagnostics_open: false,
+                diagnostics: StreamDiagnostics::default(),
+                grayscale: filter.grayscale,
+                target_size: filter.target_size,
+                filter_kind: filter.filter_kind,
+                jpeg_quality: filter.jpeg_quality,
+                posterize_enabled: filter.posterize_enabled,
+                posterize_levels: filter.posterize_levels,
+                timestamp_overlay: filter.timestamp_overlay,
             },
             Command::none(),
         )
     }
 
     fn title(&self) -> String {
-        String::from("xcoffee")
+        self.window_title.clone()
     }
 
     fn update(&mut self, message: Message) -> Command<Message> {
         match message {
-            Message::ImageLoaded(Ok(data)) => {
-                self.image_data = Some(data);
+            Message::ImageLoaded(Ok(frame)) => {
+                self.diagnostics.record_frame(
+                    frame.data.len(),
+                    frame.cumulative_bytes,
+                    frame.malformed_parts,
+                );
+                if self.recording_enabled {
+                    self.feed_recorder(&frame.data);
+                }
+                self.image_data = Some(frame.data);
                 self.status = String::new();
                 self.loading = false;
             }
@@ -67,9 +177,78 @@ impl Application for XCoffee {
             Message::Error(e) => {
                 self.status = format!("Stream error: {}", e);
                 self.loading = false;
+                self.diagnostics
+                    .set_connection_phase(ConnectionPhase::Sleeping);
+            }
+            Message::ConnectionPhaseChanged(phase) => {
+                self.status = match phase {
+                    ConnectionPhase::Connecting => "Reconnecting...".to_string(),
+                    ConnectionPhase::Streaming => "Connected. Waiting for frame...".to_string(),
+                    ConnectionPhase::Sleeping => self.status.clone(),
+                };
+                self.diagnostics.set_connection_phase(phase);
+            }
+            Message::ToggleDiagnostics(enabled) => {
+                self.diagnostics_open = enabled;
+            }
+            Message::ToggleGrayscale(enabled) => {
+                self.grayscale = enabled;
+                self.config_dirty = true;
+            }
+            Message::ResolutionChanged(size) => {
+                self.target_size = size;
+                self.config_dirty = true;
+            }
+            Message::FilterKindChanged(kind) => {
+                self.filter_kind = kind;
+                self.config_dirty = true;
+            }
+            Message::QualityChanged(quality) => {
+                self.jpeg_quality = quality;
+                self.config_dirty = true;
+            }
+            Message::TogglePosterize(enabled) => {
+                self.posterize_enabled = enabled;
+                self.config_dirty = true;
+            }
+            Message::PosterizeLevelsChanged(levels) => {
+                self.posterize_levels = levels;
+                self.config_dirty = true;
+            }
+            Message::ToggleTimestampOverlay(enabled) => {
+                self.timestamp_overlay = enabled;
+                self.config_dirty = true;
             }
-            Message::ToggleTrojanView(enabled) => {
-                self.trojan_view = enabled;
+            Message::ToggleRecording(enabled) => {
+                self.recording_enabled = enabled;
+                if !enabled {
+                    if let Some(recorder) = self.recorder.take() {
+                        if let Err(e) = recorder.finish() {
+                            self.status = format!("Failed to finalize recording: {}", e);
+                        }
+                    }
+                }
+            }
+            Message::RecordTick => {
+                if let Some(recorder) = &mut self.recorder {
+                    if let Err(e) = recorder.tick() {
+                        self.status = format!("Recording error: {}", e);
+                    }
+                }
+            }
+            Message::RecorderFpsChanged(fps) => {
+                self.recorder_settings.output_fps = fps;
+                self.config_dirty = true;
+            }
+            Message::RecorderIntervalChanged(secs) => {
+                self.recorder_settings.capture_interval_secs = secs;
+                self.config_dirty = true;
+            }
+            Message::PersistConfigTick => {
+                if self.config_dirty {
+                    self.persist_config();
+                    self.config_dirty = false;
+                }
             }
         }
         Command::none()
@@ -77,11 +256,8 @@ impl Application for XCoffee {
 
     fn view(&self) -> Element<'_, Message> {
         let image_widget = if let Some(data) = &self.image_data {
-            let processed_data = if self.trojan_view {
-                apply_trojan_filter(data).unwrap_or_else(|_| data.clone())
-            } else {
-                data.clone()
-            };
+            let stages = self.build_pipeline();
+            let processed_data = pipeline::apply(&stages, data).unwrap_or_else(|_| data.clone());
 
             container(
                 Image::new(iced::widget::image::Handle::from_memory(processed_data))
@@ -108,180 +284,125 @@ impl Application for XCoffee {
         };
 
         let checkbox_widget = container(
-            checkbox("Trojan View", self.trojan_view)
-                .on_toggle(Message::ToggleTrojanView)
-                .size(16)
-                .text_size(14),
+            row![
+                checkbox("Record Timelapse", self.recording_enabled)
+                    .on_toggle(Message::ToggleRecording)
+                    .size(16)
+                    .text_size(14),
+                checkbox("Diagnostics", self.diagnostics_open)
+                    .on_toggle(Message::ToggleDiagnostics)
+                    .size(16)
+                    .text_size(14),
+            ]
+            .spacing(20),
         )
         .padding(10)
         .width(Length::Fill)
         .center_x();
 
-        column![image_widget, checkbox_widget]
-            .width(Length::Fill)
-            .height(Length::Fill)
-            .into()
+        let mut content = column![
+            image_widget,
+            self.filter_controls(),
+            checkbox_widget,
+            self.recording_controls()
+        ]
+        .width(Length::Fill)
+        .height(Length::Fill);
+
+        if self.diagnostics_open {
+            content = content.push(self.diagnostics_panel());
+        }
+
+        content.into()
     }
 
     fn subscription(&self) -> Subscription<Message> {
         enum State {
-            Connecting,
+            Connecting(Duration),
             Streaming {
-                stream: Box<dyn Stream<Item = reqwest::Result<Bytes>> + Send + Unpin>,
-                buffer: Vec<u8>,
-                boundary: Vec<u8>,
-                is_first_frame: bool,
+                source: FrameSource,
+                delay: Duration,
             },
             Sleeping(Duration),
         }
 
         struct MjpegSub;
-        const URL: &str = "https://kaffee.hnf.de";
 
-        iced::subscription::unfold(
+        let url = self.url.clone();
+        let base_delay = self.backoff.base;
+        let max_delay = self.backoff.max;
+        let source_settings = self.source;
+
+        let mjpeg_sub = iced::subscription::unfold(
             std::any::TypeId::of::<MjpegSub>(),
-            State::Connecting,
-            move |state| async move {
-                match state {
-                    State::Connecting => match reqwest::get(URL).await {
-                        Ok(response) => {
-                            if response.status().is_success() {
-                                let content_type = response
-                                    .headers()
-                                    .get("content-type")
-                                    .and_then(|value| value.to_str().ok());
-
-                                if let Some(ct) = content_type {
-                                    if let Some(boundary_str) =
-                                        ct.split(';').find(|s| s.trim().starts_with("boundary="))
-                                    {
-                                        let boundary =
-                                            boundary_str.split('=').nth(1).unwrap_or("").trim();
-                                        if boundary.is_empty() {
-                                            (
-                                                Message::Error(
-                                                    "Empty boundary in Content-Type header"
-                                                        .to_string(),
-                                                ),
-                                                State::Sleeping(Duration::from_secs(5)),
-                                            )
-                                        } else {
-                                            let full_boundary =
-                                                format!("--{}", boundary).into_bytes();
-                                            (
-                                                Message::Error(
-                                                    "Connected. Waiting for frame...".to_string(),
-                                                ),
-                                                State::Streaming {
-                                                    stream: Box::new(response.bytes_stream()),
-                                                    buffer: Vec::new(),
-                                                    boundary: full_boundary,
-                                                    is_first_frame: true,
-                                                },
-                                            )
-                                        }
-                                    } else {
-                                        (
-                                            Message::Error(
-                                                "Boundary not found in Content-Type header"
-                                                    .to_string(),
-                                            ),
-                                            State::Sleeping(Duration::from_secs(5)),
-                                        )
-                                    }
-                                } else {
-                                    (
-                                        Message::Error("Missing Content-Type header".to_string()),
-                                        State::Sleeping(Duration::from_secs(5)),
-                                    )
-                                }
-                            } else {
-                                (
-                                    Message::Error(format!(
-                                        "Connection failed with status: {}",
-                                        response.status()
-                                    )),
-                                    State::Sleeping(Duration::from_secs(5)),
-                                )
-                            }
-                        }
-                        Err(e) => (
-                            Message::Error(format!("Connection error: {}", e)),
-                            State::Sleeping(Duration::from_secs(5)),
-                        ),
+            State::Connecting(base_delay),
+            move |state| {
+                let url = url.clone();
+                let source_config = match source_settings.kind {
+                    config::SourceKind::HttpMjpeg => SourceConfig::HttpMjpeg { url },
+                    config::SourceKind::Rtp => SourceConfig::Rtp {
+                        bind_addr: source_settings.rtp_bind_addr,
+                        codec: source_settings.rtp_codec,
                     },
-                    State::Streaming {
-                        mut stream,
-                        mut buffer,
-                        boundary,
-                        is_first_frame,
-                    } => loop {
-                        let boundary_to_search = if is_first_frame {
-                            boundary.clone()
-                        } else {
-                            [b"\r\n", boundary.as_slice()].concat()
-                        };
-
-                        if let Some(boundary_pos) = buffer
-                            .windows(boundary_to_search.len())
-                            .position(|w| w == &boundary_to_search)
-                        {
-                            let part_data = &buffer[..boundary_pos];
-                            if !part_data.is_empty() {
-                                let header_body_separator = b"\r\n\r\n";
-                                if let Some(separator_pos) = part_data
-                                    .windows(header_body_separator.len())
-                                    .position(|w| w == header_body_separator)
-                                {
-                                    let image_data = part_data
-                                        [separator_pos + header_body_separator.len()..]
-                                        .to_vec();
-                                    if !image_data.is_empty() {
-                                        buffer.drain(..boundary_pos + boundary_to_search.len());
-                                        break (
-                                            Message::ImageLoaded(Ok(image_data)),
-                                            State::Streaming {
-                                                stream,
-                                                buffer,
-                                                boundary,
-                                                is_first_frame: false,
-                                            },
-                                        );
-                                    }
-                                }
+                };
+                async move {
+                    match state {
+                        State::Connecting(delay) => {
+                            match FrameSource::connect(source_config).await {
+                                Ok(source) => (
+                                    Message::ConnectionPhaseChanged(ConnectionPhase::Streaming),
+                                    State::Streaming { source, delay },
+                                ),
+                                Err(e) => (Message::Error(e), State::Sleeping(delay)),
                             }
-                            buffer.drain(..boundary_pos + boundary_to_search.len());
-                        } else {
-                            match stream.next().await {
-                                Some(Ok(chunk)) => {
-                                    buffer.extend_from_slice(&chunk);
-                                }
-                                Some(Err(e)) => {
-                                    break (
-                                        Message::Error(format!("Stream error: {}", e)),
-                                        State::Sleeping(Duration::from_secs(5)),
-                                    );
-                                }
-                                None => {
-                                    break (
-                                        Message::Error("Stream ended. Reconnecting...".to_string()),
-                                        State::Sleeping(Duration::from_secs(5)),
-                                    );
-                                }
+                        }
+                        State::Streaming { mut source, delay } => match source.next_frame().await {
+                            Ok(data) => {
+                                let stats = source.stats();
+                                (
+                                    Message::ImageLoaded(Ok(FrameEvent {
+                                        data,
+                                        cumulative_bytes: stats.total_bytes,
+                                        malformed_parts: stats.malformed_parts,
+                                    })),
+                                    State::Streaming {
+                                        source,
+                                        delay: base_delay,
+                                    },
+                                )
                             }
+                            Err(e) => (Message::Error(e), State::Sleeping(delay)),
+                        },
+                        State::Sleeping(delay) => {
+                            // Reconnect after the current backoff delay, then
+                            // double it (capped at `max_delay`) in case the
+                            // next attempt fails too.
+                            tokio::time::sleep(delay).await;
+                            let next_delay = delay.mul_f64(2.0).min(max_delay);
+                            (
+                                Message::ConnectionPhaseChanged(ConnectionPhase::Connecting),
+                                State::Connecting(next_delay),
+                            )
                         }
-                    },
-                    State::Sleeping(duration) => {
-                        // Reconnect after a delay
-                        tokio::time::sleep(duration).await;
-                        (
-                            Message::Error("Reconnecting...".to_string()),
-                            State::Connecting,
-                        )
                     }
                 }
             },
-        )
+        );
+
+        // Debounces `persist_config`'s blocking disk write: settings changes
+        // just flip `config_dirty`, and this tick is what actually saves.
+        let persist_tick =
+            iced::time::every(Duration::from_millis(500)).map(|_| Message::PersistConfigTick);
+
+        if self.recording_enabled {
+            let interval = self.recorder_settings.capture_interval().unwrap_or_else(|| {
+                Duration::from_secs_f64(1.0 / self.recorder_settings.output_fps as f64)
+            });
+            let tick = iced::time::every(interval).map(|_| Message::RecordTick);
+            Subscription::batch([mjpeg_sub, persist_tick, tick])
+        } else {
+            Subscription::batch([mjpeg_sub, persist_tick])
+        }
     }
 
     fn theme(&self) -> Theme {
@@ -289,39 +410,273 @@ impl Application for XCoffee {
     }
 }
 
-fn apply_trojan_filter(image_data: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
-    // Load the image
-    let img = image::load_from_memory(image_data)?;
+impl XCoffee {
+    /// Builds the ordered filter pipeline from the current UI settings.
+    fn build_pipeline(&self) -> Vec<FilterStage> {
+        let mut stages = Vec::new();
+        if self.grayscale {
+            stages.push(FilterStage::Grayscale);
+        }
+        stages.push(FilterStage::Resize {
+            target_size: self.target_size,
+            filter: self.filter_kind,
+        });
+        if self.posterize_enabled {
+            stages.push(FilterStage::Posterize {
+                levels: self.posterize_levels,
+            });
+        }
+        if self.timestamp_overlay {
+            stages.push(FilterStage::TimestampOverlay {
+                label: current_timestamp_label(),
+            });
+        }
+        stages.push(FilterStage::JpegQuality(self.jpeg_quality));
+        stages
+    }
 
-    // Convert to grayscale
-    let gray_img = imageops::grayscale(&img);
+    /// Sliders/pickers for every pipeline stage's parameters, rebuilt each
+    /// `view` so adjustments apply to the next rendered frame immediately.
+    fn filter_controls(&self) -> Element<'_, Message> {
+        container(
+            column![
+                row![
+                    checkbox("Grayscale", self.grayscale)
+                        .on_toggle(Message::ToggleGrayscale)
+                        .size(16)
+                        .text_size(14),
+                    checkbox("Posterize", self.posterize_enabled)
+                        .on_toggle(Message::TogglePosterize)
+                        .size(16)
+                        .text_size(14),
+                    checkbox("Timestamp Overlay", self.timestamp_overlay)
+                        .on_toggle(Message::ToggleTimestampOverlay)
+                        .size(16)
+                        .text_size(14),
+                ]
+                .spacing(20),
+                row![
+                    text(format!("Resolution: {}px", self.target_size)).size(13),
+                    slider(32..=480, self.target_size, Message::ResolutionChanged).step(16u32),
+                ]
+                .spacing(10),
+                row![
+                    text("Filter:").size(13),
+                    pick_list(
+                        FilterKind::ALL,
+                        Some(self.filter_kind),
+                        Message::FilterKindChanged
+                    ),
+                ]
+                .spacing(10),
+                row![
+                    text(format!("JPEG quality: {}", self.jpeg_quality)).size(13),
+                    slider(1..=100, self.jpeg_quality, Message::QualityChanged),
+                ]
+                .spacing(10),
+                row![
+                    text(format!("Posterize levels: {}", self.posterize_levels)).size(13),
+                    slider(
+                        2..=16,
+                        self.posterize_levels,
+                        Message::PosterizeLevelsChanged
+                    ),
+                ]
+                .spacing(10),
+            ]
+            .spacing(6),
+        )
+        .padding(10)
+        .width(Length::Fill)
+        .into()
+    }
 
-    // Calculate dimensions to fit within 128x128 while preserving aspect ratio
-    let (orig_width, orig_height) = gray_img.dimensions();
-    let aspect_ratio = orig_width as f32 / orig_height as f32;
+    /// Sliders for the timelapse recorder's output FPS and capture
+    /// interval, alongside the "Record Timelapse" checkbox.
+    fn recording_controls(&self) -> Element<'_, Message> {
+        container(
+            row![
+                text(format!(
+                    "Timelapse FPS: {}",
+                    self.recorder_settings.output_fps
+                ))
+                .size(13),
+                slider(
+                    1..=60,
+                    self.recorder_settings.output_fps,
+                    Message::RecorderFpsChanged
+                ),
+                text(if self.recorder_settings.capture_interval_secs == 0 {
+                    "Capture: every frame".to_string()
+                } else {
+                    format!(
+                        "Capture every: {}s",
+                        self.recorder_settings.capture_interval_secs
+                    )
+                })
+                .size(13),
+                slider(
+                    0..=120,
+                    self.recorder_settings.capture_interval_secs,
+                    Message::RecorderIntervalChanged
+                ),
+            ]
+            .spacing(10),
+        )
+        .padding(10)
+        .width(Length::Fill)
+        .into()
+    }
 
-    let (new_width, new_height) = if aspect_ratio > 1.0 {
-        // Width is larger, scale to 128 width
-        (128, (128.0 / aspect_ratio) as u32)
-    } else {
-        // Height is larger or equal, scale to 128 height
-        ((128.0 * aspect_ratio) as u32, 128)
-    };
+    /// Writes the current URL, window identity, filter settings, source
+    /// type, and backoff parameters to `xcoffee.conf` so the next launch
+    /// reopens with the same choices. Only called from `PersistConfigTick`
+    /// once `config_dirty` is set, so a slider drag doesn't turn into a
+    /// blocking disk write per tick. Failures are logged but otherwise
+    /// ignored.
+    fn persist_config(&self) {
+        let config = config::AppConfig {
+            url: self.url.clone(),
+            window_title: self.window_title.clone(),
+            window_id: self.window_id.clone(),
+            initial_filter: config::FilterSettings {
+                grayscale: self.grayscale,
+                target_size: self.target_size,
+                filter_kind: self.filter_kind,
+                jpeg_quality: self.jpeg_quality,
+                posterize_enabled: self.posterize_enabled,
+                posterize_levels: self.posterize_levels,
+                timestamp_overlay: self.timestamp_overlay,
+            },
+            backoff: self.backoff,
+            source: self.source,
+            recorder: self.recorder_settings,
+        };
+        if let Err(e) = config::save(&config) {
+            eprintln!("failed to save config: {}", e);
+        }
+    }
+
+    /// Decodes a freshly-arrived JPEG part and hands it to the recorder,
+    /// creating the encoder on the first frame once the frame dimensions
+    /// are known.
+    fn feed_recorder(&mut self, data: &[u8]) {
+        let Ok(decoded) = image::load_from_memory(data) else {
+            return;
+        };
+        let rgb = decoded.to_rgb8();
 
-    // Resize using nearest neighbor for pixelated effect
-    let resized = imageops::resize(
-        &gray_img,
-        new_width,
-        new_height,
-        imageops::FilterType::Nearest,
-    );
+        if self.recorder.is_none() {
+            let (width, height) = rgb.dimensions();
+            let output_path = timelapse_output_path();
+            match RecorderState::new(
+                output_path,
+                width,
+                height,
+                self.recorder_settings.output_fps,
+                self.recorder_settings.capture_interval(),
+            ) {
+                Ok(recorder) => self.recorder = Some(recorder),
+                Err(e) => {
+                    self.status = format!("Failed to start recording: {}", e);
+                    self.recording_enabled = false;
+                    return;
+                }
+            }
+        }
+
+        if let Some(recorder) = &mut self.recorder {
+            recorder.set_latest_frame(rgb);
+        }
+    }
 
-    // Convert back to DynamicImage
-    let small_img = DynamicImage::ImageLuma8(resized);
+    /// Collapsible section with live FPS, frame size, and parser health
+    /// numbers derived from `self.diagnostics`.
+    fn diagnostics_panel(&self) -> Element<'_, Message> {
+        let fps = self
+            .diagnostics
+            .fps()
+            .map(|fps| format!("{:.1}", fps))
+            .unwrap_or_else(|| "-".to_string());
+        let last_size = self
+            .diagnostics
+            .last_frame_size()
+            .map(format_bytes)
+            .unwrap_or_else(|| "-".to_string());
+        let avg_size = self
+            .diagnostics
+            .average_frame_size()
+            .map(format_bytes)
+            .unwrap_or_else(|| "-".to_string());
+        let peak_size = self
+            .diagnostics
+            .peak_frame_size()
+            .map(format_bytes)
+            .unwrap_or_else(|| "-".to_string());
 
-    // Encode to JPEG with low quality for vintage camera effect
-    let mut output = Cursor::new(Vec::new());
-    small_img.write_to(&mut output, ImageFormat::Jpeg)?;
+        container(
+            column![
+                text(format!(
+                    "Connection: {}",
+                    self.diagnostics.connection_phase()
+                ))
+                .size(13),
+                text(format!("FPS: {}", fps)).size(13),
+                text(format!(
+                    "Frame size: last {} / avg {} / peak {}",
+                    last_size, avg_size, peak_size
+                ))
+                .size(13),
+                text(format!(
+                    "Total received: {}",
+                    format_bytes(self.diagnostics.cumulative_bytes() as usize)
+                ))
+                .size(13),
+                text(format!(
+                    "Malformed parts: {}",
+                    self.diagnostics.malformed_parts()
+                ))
+                .size(13),
+            ]
+            .spacing(4),
+        )
+        .padding(10)
+        .width(Length::Fill)
+        .into()
+    }
+}
+
+fn format_bytes(bytes: usize) -> String {
+    const KIB: f64 = 1024.0;
+    let bytes = bytes as f64;
+    if bytes < KIB {
+        format!("{} B", bytes as u64)
+    } else {
+        format!("{:.1} KiB", bytes / KIB)
+    }
+}
+
+/// Builds a timelapse output filename from the current wall-clock time.
+fn timelapse_output_path() -> String {
+    let unix_secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    format!("xcoffee-timelapse-{}.mp4", unix_secs)
+}
 
-    Ok(output.into_inner())
+/// Renders the current time of day as `HH:MM:SS` for the timestamp overlay
+/// stage, using only characters the built-in bitmap font supports.
+fn current_timestamp_label() -> String {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let day_secs = secs % 86_400;
+    format!(
+        "{:02}:{:02}:{:02}",
+        day_secs / 3600,
+        (day_secs % 3600) / 60,
+        day_secs % 60
+    )
 }
@@ -0,0 +1,129 @@
+//! Incremental multipart/x-mixed-replace parsing, factored out of the iced
+//! subscription so the same boundary-scanning logic can be reused by the
+//! headless relay server.
+
+/// Cumulative health counters for a decoder instance, surfaced by the GUI's
+/// diagnostics panel.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DecoderStats {
+    /// Total JPEG bytes handed out across all parts so far.
+    pub total_bytes: u64,
+    /// Parts where a boundary was found but the body was malformed: no
+    /// `\r\n\r\n` header separator, or an empty image body.
+    pub malformed_parts: u64,
+}
+
+/// Scans an accumulating byte buffer for `--boundary`-delimited parts and
+/// yields the JPEG body of each complete part as it becomes available.
+pub struct MjpegDecoder {
+    buffer: Vec<u8>,
+    boundary: Vec<u8>,
+    is_first_frame: bool,
+    stats: DecoderStats,
+}
+
+impl MjpegDecoder {
+    pub fn new(boundary: &str) -> Self {
+        Self {
+            buffer: Vec::new(),
+            boundary: format!("--{}", boundary).into_bytes(),
+            is_first_frame: true,
+            stats: DecoderStats::default(),
+        }
+    }
+
+    /// Cumulative stats for this decoder instance.
+    pub fn stats(&self) -> DecoderStats {
+        self.stats
+    }
+
+    /// Appends newly received bytes to the internal buffer.
+    pub fn feed(&mut self, chunk: &[u8]) {
+        self.buffer.extend_from_slice(chunk);
+    }
+
+    /// Pulls the next complete JPEG part out of the buffer, if one is
+    /// available yet. Returns `None` when more bytes are needed.
+    pub fn next_part(&mut self) -> Option<Vec<u8>> {
+        loop {
+            let boundary_to_search = if self.is_first_frame {
+                self.boundary.clone()
+            } else {
+                [b"\r\n", self.boundary.as_slice()].concat()
+            };
+
+            let boundary_pos = self
+                .buffer
+                .windows(boundary_to_search.len())
+                .position(|w| w == boundary_to_search.as_slice())?;
+
+            let part_data = &self.buffer[..boundary_pos];
+            let mut part_ok = false;
+            if !part_data.is_empty() {
+                let header_body_separator = b"\r\n\r\n";
+                if let Some(separator_pos) = part_data
+                    .windows(header_body_separator.len())
+                    .position(|w| w == header_body_separator)
+                {
+                    let image_data =
+                        part_data[separator_pos + header_body_separator.len()..].to_vec();
+                    if !image_data.is_empty() {
+                        part_ok = true;
+                        self.buffer.drain(..boundary_pos + boundary_to_search.len());
+                        self.is_first_frame = false;
+                        self.stats.total_bytes += image_data.len() as u64;
+                        return Some(image_data);
+                    }
+                }
+            }
+            if !part_ok && !part_data.is_empty() {
+                self.stats.malformed_parts += 1;
+            }
+            self.buffer.drain(..boundary_pos + boundary_to_search.len());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_a_part_fed_in_one_chunk() {
+        let mut decoder = MjpegDecoder::new("frame");
+        decoder.feed(b"--frame\r\nContent-Type: image/jpeg\r\n\r\nJPEGDATA--frame\r\n");
+        let part = decoder.next_part().expect("a part");
+        assert_eq!(part, b"JPEGDATA");
+        assert_eq!(decoder.stats().total_bytes, 8);
+    }
+
+    #[test]
+    fn decodes_a_part_split_across_chunks() {
+        let mut decoder = MjpegDecoder::new("frame");
+        decoder.feed(b"--frame\r\nContent-Type: image/jpeg\r\n\r\nJPEG");
+        assert!(decoder.next_part().is_none());
+        decoder.feed(b"DATA--frame\r\n");
+        let part = decoder.next_part().expect("a part");
+        assert_eq!(part, b"JPEGDATA");
+    }
+
+    #[test]
+    fn counts_a_malformed_part_missing_the_header_separator() {
+        let mut decoder = MjpegDecoder::new("frame");
+        decoder.feed(b"--frame\r\nmalformed--frame\r\nContent-Type: image/jpeg\r\n\r\nGOOD--frame\r\n");
+        let part = decoder.next_part().expect("the good part");
+        assert_eq!(part, b"GOOD");
+        assert_eq!(decoder.stats().malformed_parts, 1);
+    }
+
+    #[test]
+    fn counts_a_malformed_part_with_an_empty_body() {
+        let mut decoder = MjpegDecoder::new("frame");
+        decoder.feed(
+            b"--frame\r\nContent-Type: image/jpeg\r\n\r\n--frame\r\nContent-Type: image/jpeg\r\n\r\nGOOD--frame\r\n",
+        );
+        let part = decoder.next_part().expect("the good part");
+        assert_eq!(part, b"GOOD");
+        assert_eq!(decoder.stats().malformed_parts, 1);
+    }
+}
@@ -0,0 +1,197 @@
+//! Configurable image-processing pipeline applied to each decoded frame.
+//!
+//! Replaces the old fixed "Trojan View" (grayscale -> fit to 128x128
+//! nearest-neighbor -> low-quality JPEG) with a composable, ordered list of
+//! stages. Each stage is a small, independently adjustable transform; `view`
+//! rebuilds the list from the current UI settings and re-applies it to the
+//! latest frame, so slider/picker changes take effect live.
+
+use image::{imageops, imageops::FilterType, DynamicImage, Rgba, RgbaImage};
+use std::io::Cursor;
+
+/// A `FilterType` wrapper that implements `Display` so it can be used
+/// directly as a `pick_list` option.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterKind {
+    Nearest,
+    Triangle,
+    Lanczos3,
+}
+
+impl FilterKind {
+    pub const ALL: [FilterKind; 3] = [
+        FilterKind::Nearest,
+        FilterKind::Triangle,
+        FilterKind::Lanczos3,
+    ];
+
+    fn to_image_filter(self) -> FilterType {
+        match self {
+            FilterKind::Nearest => FilterType::Nearest,
+            FilterKind::Triangle => FilterType::Triangle,
+            FilterKind::Lanczos3 => FilterType::Lanczos3,
+        }
+    }
+}
+
+impl std::fmt::Display for FilterKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let label = match self {
+            FilterKind::Nearest => "Nearest (pixelated)",
+            FilterKind::Triangle => "Triangle (smooth)",
+            FilterKind::Lanczos3 => "Lanczos3 (sharp)",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// One stage of the pipeline. Stages are applied in order by `apply`.
+#[derive(Debug, Clone)]
+pub enum FilterStage {
+    Grayscale,
+    /// Fits the image within a `target_size`x`target_size` box, preserving
+    /// aspect ratio, using the given resampling filter.
+    Resize {
+        target_size: u32,
+        filter: FilterKind,
+    },
+    /// Quantizes each color channel down to `levels` steps for a
+    /// posterized look; no dithering is applied.
+    Posterize {
+        levels: u8,
+    },
+    /// Burns `label` into the bottom-left corner of the frame.
+    TimestampOverlay {
+        label: String,
+    },
+    /// Not applied in-place; read by `apply` to pick the final JPEG quality.
+    JpegQuality(u8),
+}
+
+/// Applies `stages` in order to a JPEG frame and re-encodes the result,
+/// using the last `JpegQuality` stage seen (or 85 if none is present).
+pub fn apply(
+    stages: &[FilterStage],
+    image_data: &[u8],
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut img = image::load_from_memory(image_data)?;
+    let mut quality: u8 = 85;
+
+    for stage in stages {
+        match stage {
+            FilterStage::Grayscale => {
+                img = DynamicImage::ImageLuma8(imageops::grayscale(&img));
+            }
+            FilterStage::Resize {
+                target_size,
+                filter,
+            } => {
+                img = resize_to_fit(&img, *target_size, filter.to_image_filter());
+            }
+            FilterStage::Posterize { levels } => {
+                img = posterize(&img, *levels);
+            }
+            FilterStage::TimestampOverlay { label } => {
+                img = overlay_label(img, label);
+            }
+            FilterStage::JpegQuality(q) => {
+                quality = *q;
+            }
+        }
+    }
+
+    let mut output = Cursor::new(Vec::new());
+    let mut encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(&mut output, quality);
+    encoder.encode_image(&img)?;
+    Ok(output.into_inner())
+}
+
+fn resize_to_fit(img: &DynamicImage, target_size: u32, filter: FilterType) -> DynamicImage {
+    let (orig_width, orig_height) = (img.width(), img.height());
+    let aspect_ratio = orig_width as f32 / orig_height as f32;
+
+    let (new_width, new_height) = if aspect_ratio > 1.0 {
+        (target_size, (target_size as f32 / aspect_ratio) as u32)
+    } else {
+        ((target_size as f32 * aspect_ratio) as u32, target_size)
+    };
+
+    img.resize_exact(new_width.max(1), new_height.max(1), filter)
+}
+
+/// Quantizes each RGB channel to `levels` evenly-spaced steps (rounding
+/// each pixel on its own, without dithering), for a 1990s webcam look.
+fn posterize(img: &DynamicImage, levels: u8) -> DynamicImage {
+    let levels = levels.max(2);
+    let step = 255.0 / (levels - 1) as f64;
+    let rgba = img.to_rgba8();
+
+    let mut out = RgbaImage::new(rgba.width(), rgba.height());
+    let quantize = |c: u8| -> u8 {
+        let level = (c as f64 / step).round();
+        (level * step).round().clamp(0.0, 255.0) as u8
+    };
+    for (x, y, px) in rgba.enumerate_pixels() {
+        let Rgba([r, g, b, a]) = *px;
+        out.put_pixel(x, y, Rgba([quantize(r), quantize(g), quantize(b), a]));
+    }
+    DynamicImage::ImageRgba8(out)
+}
+
+/// Tiny built-in 3x5 bitmap font covering the characters needed for a
+/// timestamp label, so the overlay needs no font file or extra dependency.
+fn glyph_bits(c: char) -> [u8; 5] {
+    match c {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b010, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        _ => [0b000, 0b000, 0b000, 0b000, 0b000],
+    }
+}
+
+/// Burns `label` into the bottom-left corner using the built-in bitmap font.
+fn overlay_label(img: DynamicImage, label: &str) -> DynamicImage {
+    const SCALE: u32 = 2;
+    const GLYPH_COLS: u32 = 3;
+    const GLYPH_ROWS: u32 = 5;
+    const GLYPH_SPACING: u32 = 1;
+    const MARGIN: u32 = 4;
+
+    let mut rgba = img.to_rgba8();
+    let (img_w, img_h) = rgba.dimensions();
+    let text_height = GLYPH_ROWS * SCALE;
+    let y0 = img_h.saturating_sub(text_height + MARGIN);
+    let mut x = MARGIN;
+
+    for c in label.chars() {
+        let bits = glyph_bits(c);
+        for (row, bits_row) in bits.iter().enumerate() {
+            for col in 0..GLYPH_COLS {
+                if bits_row & (1 << (GLYPH_COLS - 1 - col)) == 0 {
+                    continue;
+                }
+                for sy in 0..SCALE {
+                    for sx in 0..SCALE {
+                        let px = x + col * SCALE + sx;
+                        let py = y0 + row as u32 * SCALE + sy;
+                        if px < img_w && py < img_h {
+                            rgba.put_pixel(px, py, Rgba([255, 255, 0, 255]));
+                        }
+                    }
+                }
+            }
+        }
+        x += (GLYPH_COLS + GLYPH_SPACING) * SCALE;
+    }
+
+    DynamicImage::ImageRgba8(rgba)
+}
@@ -0,0 +1,196 @@
+//! Timelapse recording of the live stream to an H.264 MP4.
+//!
+//! MJPEG frames arrive irregularly, so instead of encoding every decoded
+//! frame as it arrives, the most recent frame is buffered and a fixed
+//! timer tick (`RecorderState::tick`) pulls it into the encoder. That
+//! keeps the output frame rate constant regardless of upstream jitter, and
+//! lets a sparse `capture_interval` (e.g. one frame every 10s) produce a
+//! sped-up day-long timelapse instead of a real-time recording.
+
+use ffmpeg_next as ffmpeg;
+use image::RgbImage;
+use std::time::{Duration, Instant};
+
+/// Default output frame rate of the recorded MP4.
+pub const DEFAULT_OUTPUT_FPS: u32 = 30;
+
+/// Default spacing between captured frames. `None` captures every tick at
+/// `DEFAULT_OUTPUT_FPS` instead of sampling sparsely.
+pub const DEFAULT_CAPTURE_INTERVAL: Option<Duration> = Some(Duration::from_secs(10));
+
+pub struct RecorderState {
+    encoder: ffmpeg::encoder::Video,
+    output: ffmpeg::format::context::Output,
+    stream_index: usize,
+    output_path: String,
+    output_fps: u32,
+    capture_interval: Duration,
+    pending_frame: Option<RgbImage>,
+    last_capture: Option<Instant>,
+    frame_count: u64,
+    started_at: Instant,
+    finished: bool,
+}
+
+impl RecorderState {
+    pub fn new(
+        output_path: String,
+        width: u32,
+        height: u32,
+        output_fps: u32,
+        capture_interval: Option<Duration>,
+    ) -> Result<Self, ffmpeg::Error> {
+        ffmpeg::init()?;
+
+        let mut output = ffmpeg::format::output(&output_path)?;
+        let codec =
+            ffmpeg::encoder::find(ffmpeg::codec::Id::H264).ok_or(ffmpeg::Error::EncoderNotFound)?;
+
+        let mut stream = output.add_stream(codec)?;
+        let stream_index = stream.index();
+
+        let context = ffmpeg::codec::context::Context::new_with_codec(codec);
+        let mut encoder = context.encoder().video()?;
+        encoder.set_width(width);
+        encoder.set_height(height);
+        encoder.set_format(ffmpeg::format::Pixel::YUV420P);
+        encoder.set_time_base(ffmpeg::Rational(1, output_fps as i32));
+        encoder.set_frame_rate(Some(ffmpeg::Rational(output_fps as i32, 1)));
+
+        let encoder = encoder.open_as(codec)?;
+        stream.set_parameters(&encoder);
+        output.write_header()?;
+
+        Ok(Self {
+            encoder,
+            output,
+            stream_index,
+            output_path,
+            output_fps,
+            capture_interval: capture_interval
+                .unwrap_or_else(|| Duration::from_secs_f64(1.0 / output_fps as f64)),
+            pending_frame: None,
+            last_capture: None,
+            frame_count: 0,
+            started_at: Instant::now(),
+            finished: false,
+        })
+    }
+
+    /// Replaces the buffered "latest frame" with a newly decoded one. Only
+    /// the frame present at the next `tick` is actually encoded.
+    pub fn set_latest_frame(&mut self, frame: RgbImage) {
+        self.pending_frame = Some(frame);
+    }
+
+    pub fn output_path(&self) -> &str {
+        &self.output_path
+    }
+
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+
+    /// Called on a fixed timer; if `capture_interval` has elapsed and a
+    /// frame is buffered, encodes it at `output_fps`'s next timestamp.
+    pub fn tick(&mut self) -> Result<(), ffmpeg::Error> {
+        let due = self
+            .last_capture
+            .map(|last| last.elapsed() >= self.capture_interval)
+            .unwrap_or(true);
+        if !due {
+            return Ok(());
+        }
+
+        let Some(frame) = self.pending_frame.take() else {
+            return Ok(());
+        };
+
+        let mut video_frame = to_yuv420p_frame(&frame, self.encoder.format())?;
+        video_frame.set_pts(Some(self.frame_count as i64));
+
+        self.encoder.send_frame(&video_frame)?;
+        self.drain_encoder()?;
+
+        self.frame_count += 1;
+        self.last_capture = Some(Instant::now());
+        Ok(())
+    }
+
+    fn drain_encoder(&mut self) -> Result<(), ffmpeg::Error> {
+        let mut packet = ffmpeg::Packet::empty();
+        while self.encoder.receive_packet(&mut packet).is_ok() {
+            packet.set_stream(self.stream_index);
+            packet.rescale_ts(
+                ffmpeg::Rational(1, self.output_fps as i32),
+                self.output.stream(self.stream_index).unwrap().time_base(),
+            );
+            packet.write_interleaved(&mut self.output)?;
+        }
+        Ok(())
+    }
+
+    /// Flushes the encoder and writes the MP4 trailer. Safe to call once;
+    /// also invoked from `Drop` as a safety net if the app exits without
+    /// toggling recording off first.
+    pub fn finish(mut self) -> Result<(), ffmpeg::Error> {
+        self.finalize()
+    }
+
+    fn finalize(&mut self) -> Result<(), ffmpeg::Error> {
+        if self.finished {
+            return Ok(());
+        }
+        self.encoder.send_eof()?;
+        self.drain_encoder()?;
+        self.output.write_trailer()?;
+        self.finished = true;
+        Ok(())
+    }
+}
+
+impl Drop for RecorderState {
+    fn drop(&mut self) {
+        if let Err(e) = self.finalize() {
+            eprintln!("recorder: failed to finalize {}: {}", self.output_path, e);
+        }
+    }
+}
+
+fn to_yuv420p_frame(
+    rgb: &RgbImage,
+    format: ffmpeg::format::Pixel,
+) -> Result<ffmpeg::frame::Video, ffmpeg::Error> {
+    let (width, height) = rgb.dimensions();
+    let mut rgb_frame = ffmpeg::frame::Video::new(ffmpeg::format::Pixel::RGB24, width, height);
+
+    // `Video::new` pads each row's linesize to ffmpeg's buffer alignment, so
+    // the plane generally isn't tightly packed like `rgb.as_raw()` is; copy
+    // row by row using the frame's actual stride instead of one flat copy.
+    let row_bytes = width as usize * 3;
+    let stride = rgb_frame.stride(0);
+    let src = rgb.as_raw();
+    let dst = rgb_frame.data_mut(0);
+    for row in 0..height as usize {
+        let src_row = &src[row * row_bytes..row * row_bytes + row_bytes];
+        let dst_row = &mut dst[row * stride..row * stride + row_bytes];
+        dst_row.copy_from_slice(src_row);
+    }
+
+    let mut yuv_frame = ffmpeg::frame::Video::new(format, width, height);
+    let mut converter = ffmpeg::software::scaling::Context::get(
+        ffmpeg::format::Pixel::RGB24,
+        width,
+        height,
+        format,
+        width,
+        height,
+        ffmpeg::software::scaling::Flags::BILINEAR,
+    )?;
+    converter.run(&rgb_frame, &mut yuv_frame)?;
+    Ok(yuv_frame)
+}
@@ -0,0 +1,158 @@
+//! Headless re-broadcast server: pulls the upstream MJPEG stream once and
+//! fans it out to any number of local `multipart/x-mixed-replace` clients,
+//! so a browser (or several) can watch the coffee pot without each opening
+//! its own connection to the upstream.
+
+use crate::mjpeg::MjpegDecoder;
+use futures_util::StreamExt;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{Mutex, Notify, RwLock};
+
+const RELAY_BOUNDARY: &str = "xcoffeeframe";
+
+/// Shared state that every accepted client fans out from: the latest
+/// decoded JPEG part, a notification fired whenever a new part lands, a
+/// count of currently connected clients used to gate the upstream pull,
+/// and a single-flight guard so at most one puller task ever runs.
+struct State {
+    part: RwLock<Vec<u8>>,
+    part_ready: Notify,
+    clients: AtomicUsize,
+    puller_running: Mutex<bool>,
+}
+
+/// Binds `bind_addr` and serves `multipart/x-mixed-replace` to every
+/// connecting client, re-broadcasting frames pulled from `upstream_url`.
+/// Only one upstream connection is held open at a time, and it is only
+/// opened while at least one client is connected.
+pub async fn serve(upstream_url: String, bind_addr: SocketAddr) -> std::io::Result<()> {
+    let state = Arc::new(State {
+        part: RwLock::new(Vec::new()),
+        part_ready: Notify::new(),
+        clients: AtomicUsize::new(0),
+        puller_running: Mutex::new(false),
+    });
+
+    let listener = TcpListener::bind(bind_addr).await?;
+    println!("xcoffee relay listening on http://{}", bind_addr);
+    loop {
+        let (socket, _) = listener.accept().await?;
+        let state = Arc::clone(&state);
+        let upstream_url = upstream_url.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_client(socket, state, upstream_url).await {
+                eprintln!("relay: client error: {}", e);
+            }
+        });
+    }
+}
+
+async fn handle_client(
+    mut socket: TcpStream,
+    state: Arc<State>,
+    upstream_url: String,
+) -> std::io::Result<()> {
+    // We only serve one thing, so the request itself can be discarded.
+    let mut discard = [0u8; 1024];
+    let _ = socket.read(&mut discard).await;
+
+    let header = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: multipart/x-mixed-replace; boundary={RELAY_BOUNDARY}\r\n\r\n"
+    );
+    socket.write_all(header.as_bytes()).await?;
+
+    state.clients.fetch_add(1, Ordering::SeqCst);
+    {
+        let mut running = state.puller_running.lock().await;
+        if !*running {
+            *running = true;
+            spawn_upstream_puller(Arc::clone(&state), upstream_url);
+        }
+    }
+
+    let result = stream_parts_to_client(&mut socket, &state).await;
+    state.clients.fetch_sub(1, Ordering::SeqCst);
+    result
+}
+
+async fn stream_parts_to_client(socket: &mut TcpStream, state: &State) -> std::io::Result<()> {
+    loop {
+        state.part_ready.notified().await;
+        let part = state.part.read().await.clone();
+        if part.is_empty() {
+            continue;
+        }
+        let part_header = format!(
+            "--{RELAY_BOUNDARY}\r\nContent-Type: image/jpeg\r\nContent-Length: {}\r\n\r\n",
+            part.len()
+        );
+        socket.write_all(part_header.as_bytes()).await?;
+        socket.write_all(&part).await?;
+        socket.write_all(b"\r\n").await?;
+    }
+}
+
+/// Spawns the single upstream reader, which keeps pulling frames into the
+/// shared state for as long as at least one client is attached, and exits
+/// once the last client disconnects so the upstream connection is dropped.
+/// Only called while holding `state.puller_running` locked with it set to
+/// `true`, so at most one of these ever runs; before actually stopping it
+/// re-checks the client count under that same lock so a client that
+/// connects in the exact instant the puller would otherwise exit doesn't
+/// race ahead and spawn a second, concurrent puller.
+fn spawn_upstream_puller(state: Arc<State>, upstream_url: String) {
+    tokio::spawn(async move {
+        loop {
+            while state.clients.load(Ordering::SeqCst) > 0 {
+                if let Err(e) = pull_upstream_once(&state, &upstream_url).await {
+                    eprintln!("relay: upstream error: {}", e);
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                }
+            }
+            let mut running = state.puller_running.lock().await;
+            if state.clients.load(Ordering::SeqCst) > 0 {
+                continue;
+            }
+            *running = false;
+            return;
+        }
+    });
+}
+
+async fn pull_upstream_once(
+    state: &State,
+    upstream_url: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let response = reqwest::get(upstream_url).await?;
+    let content_type = response
+        .headers()
+        .get("content-type")
+        .and_then(|v| v.to_str().ok())
+        .ok_or("missing Content-Type header")?;
+    let boundary = content_type
+        .split(';')
+        .find(|s| s.trim().starts_with("boundary="))
+        .and_then(|s| s.split('=').nth(1))
+        .ok_or("boundary not found in Content-Type header")?
+        .trim();
+
+    let mut decoder = MjpegDecoder::new(boundary);
+    let mut stream = response.bytes_stream();
+    while state.clients.load(Ordering::SeqCst) > 0 {
+        while let Some(part) = decoder.next_part() {
+            *state.part.write().await = part;
+            state.part_ready.notify_waiters();
+        }
+        match stream.next().await {
+            Some(Ok(chunk)) => decoder.feed(&chunk),
+            Some(Err(e)) => return Err(Box::new(e)),
+            None => return Err("upstream stream ended".into()),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,194 @@
+//! Minimal RTP packet parsing and video payload reassembly.
+//!
+//! Only the fields needed to group packets into a coded picture are
+//! handled: the sequence number (for gap detection), the timestamp
+//! (packets sharing one timestamp belong to the same picture), and the
+//! marker bit (set on the last packet of a picture). CSRC lists and RTP
+//! header extensions are skipped over but not otherwise interpreted.
+
+const FIXED_HEADER_LEN: usize = 12;
+
+/// A parsed RTP packet; `payload` is the VP8/VP9 payload after the fixed
+/// header, any CSRC identifiers, and any header extension.
+pub struct RtpPacket<'a> {
+    pub sequence_number: u16,
+    pub timestamp: u32,
+    pub marker: bool,
+    pub payload: &'a [u8],
+}
+
+impl<'a> RtpPacket<'a> {
+    pub fn parse(data: &'a [u8]) -> Option<Self> {
+        if data.len() < FIXED_HEADER_LEN {
+            return None;
+        }
+        if data[0] >> 6 != 2 {
+            return None; // not RTP version 2
+        }
+
+        let csrc_count = (data[0] & 0x0f) as usize;
+        let has_extension = data[0] & 0x10 != 0;
+        let marker = data[1] & 0x80 != 0;
+        let sequence_number = u16::from_be_bytes([data[2], data[3]]);
+        let timestamp = u32::from_be_bytes([data[4], data[5], data[6], data[7]]);
+
+        let mut offset = FIXED_HEADER_LEN + csrc_count * 4;
+        if has_extension {
+            if data.len() < offset + 4 {
+                return None;
+            }
+            let ext_len_words = u16::from_be_bytes([data[offset + 2], data[offset + 3]]) as usize;
+            offset += 4 + ext_len_words * 4;
+        }
+        if offset > data.len() {
+            return None;
+        }
+
+        Some(Self {
+            sequence_number,
+            timestamp,
+            marker,
+            payload: &data[offset..],
+        })
+    }
+}
+
+/// Reassembles coded pictures from RTP packets sharing a timestamp.
+/// Packets already received for the current picture are buffered and
+/// sorted by sequence number before being concatenated, so packets that
+/// arrive out of order ahead of the marker packet are put back in place
+/// rather than triggering a gap. Finalization itself still happens the
+/// instant the marker packet (the last one sent) is processed, so a
+/// marker that overtakes an in-flight lower-sequence packet will finalize
+/// early and the picture is dropped as gapped even though the straggler
+/// would have arrived a moment later; there's no grace period.
+#[derive(Default)]
+pub struct FrameReassembler {
+    current_timestamp: Option<u32>,
+    packets: Vec<(u16, Vec<u8>)>,
+    marker_seen: bool,
+}
+
+impl FrameReassembler {
+    /// Feeds one RTP packet. Returns `Some(picture)` once the marker
+    /// packet for a picture has been seen and its packets are gap-free.
+    pub fn push(&mut self, packet: RtpPacket<'_>) -> Option<Vec<u8>> {
+        if self.current_timestamp != Some(packet.timestamp) {
+            self.current_timestamp = Some(packet.timestamp);
+            self.packets.clear();
+            self.marker_seen = false;
+        }
+
+        self.packets
+            .push((packet.sequence_number, packet.payload.to_vec()));
+        if packet.marker {
+            self.marker_seen = true;
+        }
+
+        if !self.marker_seen {
+            return None;
+        }
+
+        self.current_timestamp = None;
+        let mut packets = std::mem::take(&mut self.packets);
+        // Order by distance back from the marker packet, so a picture whose
+        // sequence numbers wrap past 65535 still sorts correctly.
+        let marker_seq = packet.sequence_number;
+        packets.sort_by_key(|(sequence_number, _)| {
+            std::cmp::Reverse(marker_seq.wrapping_sub(*sequence_number))
+        });
+
+        let has_gap = packets
+            .windows(2)
+            .any(|pair| pair[1].0 != pair[0].0.wrapping_add(1));
+        if has_gap {
+            return None;
+        }
+
+        let coded_picture = packets
+            .into_iter()
+            .flat_map(|(_, payload)| payload)
+            .collect();
+        Some(coded_picture)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packet(
+        sequence_number: u16,
+        timestamp: u32,
+        marker: bool,
+        payload: &'static [u8],
+    ) -> RtpPacket<'static> {
+        RtpPacket {
+            sequence_number,
+            timestamp,
+            marker,
+            payload,
+        }
+    }
+
+    #[test]
+    fn parse_reads_the_fixed_header() {
+        let mut raw = vec![0x80, 0x00, 0x00, 0x05, 0, 0, 0, 42, 0, 0, 0, 0];
+        raw.extend_from_slice(b"payload");
+        let packet = RtpPacket::parse(&raw).expect("valid header");
+        assert_eq!(packet.sequence_number, 5);
+        assert_eq!(packet.timestamp, 42);
+        assert!(!packet.marker);
+        assert_eq!(packet.payload, b"payload");
+    }
+
+    #[test]
+    fn parse_rejects_a_packet_shorter_than_the_fixed_header() {
+        assert!(RtpPacket::parse(&[0u8; 4]).is_none());
+    }
+
+    #[test]
+    fn reassembles_in_order_packets() {
+        let mut reassembler = FrameReassembler::default();
+        assert!(reassembler.push(packet(1, 100, false, b"AB")).is_none());
+        assert!(reassembler.push(packet(2, 100, false, b"CD")).is_none());
+        let picture = reassembler.push(packet(3, 100, true, b"EF")).unwrap();
+        assert_eq!(picture, b"ABCDEF");
+    }
+
+    #[test]
+    fn reorders_stragglers_that_arrive_before_the_marker() {
+        let mut reassembler = FrameReassembler::default();
+        assert!(reassembler.push(packet(2, 100, false, b"CD")).is_none());
+        assert!(reassembler.push(packet(1, 100, false, b"AB")).is_none());
+        let picture = reassembler.push(packet(3, 100, true, b"EF")).unwrap();
+        assert_eq!(picture, b"ABCDEF");
+    }
+
+    #[test]
+    fn drops_a_picture_with_a_genuine_sequence_gap() {
+        let mut reassembler = FrameReassembler::default();
+        assert!(reassembler.push(packet(1, 100, false, b"AB")).is_none());
+        // Packet 2 never arrives.
+        assert!(reassembler.push(packet(3, 100, true, b"EF")).is_none());
+    }
+
+    #[test]
+    fn marker_overtaking_an_in_flight_straggler_drops_the_picture() {
+        let mut reassembler = FrameReassembler::default();
+        assert!(reassembler.push(packet(1, 100, false, b"AB")).is_none());
+        // The marker (packet 3) overtakes packet 2, which is still in
+        // flight; per the documented limitation this finalizes early and
+        // the picture is dropped rather than waiting for packet 2.
+        assert!(reassembler.push(packet(3, 100, true, b"EF")).is_none());
+    }
+
+    #[test]
+    fn a_new_timestamp_resets_the_in_progress_picture() {
+        let mut reassembler = FrameReassembler::default();
+        assert!(reassembler.push(packet(1, 100, false, b"AB")).is_none());
+        assert!(reassembler.push(packet(1, 200, false, b"XY")).is_none());
+        let picture = reassembler.push(packet(2, 200, true, b"Z!")).unwrap();
+        assert_eq!(picture, b"XYZ!");
+    }
+}
@@ -0,0 +1,277 @@
+//! Frame sources: wire-format-specific state machines that all produce the
+//! same thing — one still-image frame per call — so the GUI and filter
+//! pipeline never need to know whether a frame came from HTTP multipart
+//! MJPEG or a depayloaded RTP stream.
+
+use crate::mjpeg::MjpegDecoder;
+use crate::rtp::{FrameReassembler, RtpPacket};
+use bytes::Bytes;
+use futures_util::{Stream, StreamExt};
+use image::{ImageBuffer, Rgb};
+use std::io::Cursor;
+use std::net::SocketAddr;
+use tokio::net::UdpSocket;
+
+/// Which video codec the RTP source should depayload and decode.
+#[derive(Debug, Clone, Copy)]
+pub enum RtpVideoCodec {
+    Vp8,
+    Vp9,
+}
+
+/// Selects which source implementation `FrameSource::connect` builds.
+#[derive(Debug, Clone)]
+pub enum SourceConfig {
+    HttpMjpeg {
+        url: String,
+    },
+    Rtp {
+        bind_addr: SocketAddr,
+        codec: RtpVideoCodec,
+    },
+}
+
+/// Cumulative health counters, generalized across source types so the
+/// diagnostics panel doesn't need to know which one is active.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SourceStats {
+    pub total_bytes: u64,
+    /// MJPEG: parts with a boundary but no valid body. RTP: gap-free
+    /// pictures the decoder could not decode; pictures dropped because of
+    /// a sequence-number gap are not counted.
+    pub malformed_parts: u64,
+}
+
+/// A connected frame source. Every variant yields decoded, JPEG-encoded
+/// still images via `next_frame`, regardless of wire format.
+pub enum FrameSource {
+    HttpMjpeg(HttpMjpegSource),
+    Rtp(RtpSource),
+}
+
+impl FrameSource {
+    pub async fn connect(config: SourceConfig) -> Result<Self, String> {
+        match config {
+            SourceConfig::HttpMjpeg { url } => HttpMjpegSource::connect(&url)
+                .await
+                .map(FrameSource::HttpMjpeg),
+            SourceConfig::Rtp { bind_addr, codec } => RtpSource::connect(bind_addr, codec)
+                .await
+                .map(FrameSource::Rtp),
+        }
+    }
+
+    /// Waits for and returns the next complete frame.
+    pub async fn next_frame(&mut self) -> Result<Vec<u8>, String> {
+        match self {
+            FrameSource::HttpMjpeg(source) => source.next_frame().await,
+            FrameSource::Rtp(source) => source.next_frame().await,
+        }
+    }
+
+    pub fn stats(&self) -> SourceStats {
+        match self {
+            FrameSource::HttpMjpeg(source) => {
+                let stats = source.decoder.stats();
+                SourceStats {
+                    total_bytes: stats.total_bytes,
+                    malformed_parts: stats.malformed_parts,
+                }
+            }
+            FrameSource::Rtp(source) => SourceStats {
+                total_bytes: source.total_bytes,
+                malformed_parts: source.dropped_pictures,
+            },
+        }
+    }
+}
+
+/// HTTP `multipart/x-mixed-replace` source: the original connection
+/// established in `FrameSource::connect`, decoded incrementally by a
+/// [`MjpegDecoder`].
+pub struct HttpMjpegSource {
+    stream: Box<dyn Stream<Item = reqwest::Result<Bytes>> + Send + Unpin>,
+    decoder: MjpegDecoder,
+}
+
+impl HttpMjpegSource {
+    async fn connect(url: &str) -> Result<Self, String> {
+        let response = reqwest::get(url)
+            .await
+            .map_err(|e| format!("Connection error: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!(
+                "Connection failed with status: {}",
+                response.status()
+            ));
+        }
+
+        let content_type = response
+            .headers()
+            .get("content-type")
+            .and_then(|value| value.to_str().ok())
+            .ok_or_else(|| "Missing Content-Type header".to_string())?;
+
+        let boundary = content_type
+            .split(';')
+            .find(|s| s.trim().starts_with("boundary="))
+            .and_then(|s| s.split('=').nth(1))
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .ok_or_else(|| "Boundary not found in Content-Type header".to_string())?;
+
+        Ok(Self {
+            stream: Box::new(response.bytes_stream()),
+            decoder: MjpegDecoder::new(boundary),
+        })
+    }
+
+    async fn next_frame(&mut self) -> Result<Vec<u8>, String> {
+        loop {
+            if let Some(part) = self.decoder.next_part() {
+                return Ok(part);
+            }
+            match self.stream.next().await {
+                Some(Ok(chunk)) => self.decoder.feed(&chunk),
+                Some(Err(e)) => return Err(format!("Stream error: {}", e)),
+                None => return Err("Stream ended. Reconnecting...".to_string()),
+            }
+        }
+    }
+}
+
+/// RTP source: listens for VP8/VP9 RTP packets on a UDP socket, reassembles
+/// coded pictures, and decodes each into an RGB still image.
+pub struct RtpSource {
+    socket: UdpSocket,
+    reassembler: FrameReassembler,
+    decoder: VpxDecoder,
+    total_bytes: u64,
+    dropped_pictures: u64,
+}
+
+impl RtpSource {
+    async fn connect(bind_addr: SocketAddr, codec: RtpVideoCodec) -> Result<Self, String> {
+        let socket = UdpSocket::bind(bind_addr)
+            .await
+            .map_err(|e| format!("RTP bind error: {}", e))?;
+        let decoder = VpxDecoder::new(codec)?;
+
+        Ok(Self {
+            socket,
+            reassembler: FrameReassembler::default(),
+            decoder,
+            total_bytes: 0,
+            dropped_pictures: 0,
+        })
+    }
+
+    async fn next_frame(&mut self) -> Result<Vec<u8>, String> {
+        let mut buf = [0u8; 1500];
+        loop {
+            let (len, _) = self
+                .socket
+                .recv_from(&mut buf)
+                .await
+                .map_err(|e| format!("RTP recv error: {}", e))?;
+
+            let Some(packet) = RtpPacket::parse(&buf[..len]) else {
+                continue;
+            };
+            let Some(coded_picture) = self.reassembler.push(packet) else {
+                continue;
+            };
+
+            match self.decoder.decode_to_jpeg(&coded_picture) {
+                Ok(jpeg) => {
+                    self.total_bytes += jpeg.len() as u64;
+                    return Ok(jpeg);
+                }
+                Err(_) => {
+                    // Gap-free but still undecodable (e.g. waiting on a
+                    // keyframe); count it and keep listening.
+                    self.dropped_pictures += 1;
+                }
+            }
+        }
+    }
+}
+
+/// Thin wrapper around a VP8/VP9 decoder, producing JPEG bytes so the rest
+/// of the app can treat every source identically.
+struct VpxDecoder {
+    inner: vpx_decode::Decoder,
+}
+
+impl VpxDecoder {
+    fn new(codec: RtpVideoCodec) -> Result<Self, String> {
+        let vpx_codec = match codec {
+            RtpVideoCodec::Vp8 => vpx_decode::Codec::VP8,
+            RtpVideoCodec::Vp9 => vpx_decode::Codec::VP9,
+        };
+        let inner =
+            vpx_decode::Decoder::new(vpx_codec).map_err(|e| format!("VPX init error: {:?}", e))?;
+        Ok(Self { inner })
+    }
+
+    fn decode_to_jpeg(&mut self, coded_picture: &[u8]) -> Result<Vec<u8>, String> {
+        let mut frames = self
+            .inner
+            .decode(coded_picture)
+            .map_err(|e| format!("VPX decode error: {:?}", e))?;
+        let picture = frames
+            .next()
+            .ok_or_else(|| "no picture decoded".to_string())?;
+
+        let rgb = yuv420_to_rgb(
+            picture.width(),
+            picture.height(),
+            picture.plane(0),
+            picture.plane(1),
+            picture.plane(2),
+            picture.stride(0),
+            picture.stride(1),
+            picture.stride(2),
+        );
+
+        let mut output = Cursor::new(Vec::new());
+        let mut encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(&mut output, 85);
+        encoder
+            .encode_image(&rgb)
+            .map_err(|e| format!("JPEG encode error: {}", e))?;
+        Ok(output.into_inner())
+    }
+}
+
+/// Converts a planar YUV 4:2:0 picture (BT.601, studio-range-agnostic,
+/// computed in floating point) into an RGB image, for frames coming off a video
+/// decoder rather than already-encoded JPEG.
+fn yuv420_to_rgb(
+    width: u32,
+    height: u32,
+    y_plane: &[u8],
+    u_plane: &[u8],
+    v_plane: &[u8],
+    y_stride: usize,
+    u_stride: usize,
+    v_stride: usize,
+) -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+    let mut rgb = ImageBuffer::new(width, height);
+
+    for row in 0..height as usize {
+        for col in 0..width as usize {
+            let y = y_plane[row * y_stride + col] as f32;
+            let u = u_plane[(row / 2) * u_stride + col / 2] as f32 - 128.0;
+            let v = v_plane[(row / 2) * v_stride + col / 2] as f32 - 128.0;
+
+            let r = (y + 1.402 * v).clamp(0.0, 255.0) as u8;
+            let g = (y - 0.344136 * u - 0.714136 * v).clamp(0.0, 255.0) as u8;
+            let b = (y + 1.772 * u).clamp(0.0, 255.0) as u8;
+
+            rgb.put_pixel(col as u32, row as u32, Rgb([r, g, b]));
+        }
+    }
+
+    rgb
+}